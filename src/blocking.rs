@@ -0,0 +1,81 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::error_handler::ApiError;
+
+/// Maximum number of attempts (initial try + retries) for a DB operation.
+const DB_MAX_ATTEMPTS: u32 = 3;
+/// Base delay for the exponential backoff between retries.
+const DB_BASE_BACKOFF: Duration = Duration::from_millis(50);
+/// Overall deadline across all attempts; once exceeded we stop retrying.
+const DB_DEADLINE: Duration = Duration::from_secs(2);
+
+/// Whether an error is connection-class — a pool checkout timeout or a
+/// connect/timeout/DNS failure from the underlying reqwest client — and thus
+/// worth retrying. Query and validation failures are deterministic and are
+/// never retried.
+fn is_connection_error(e: &ApiError) -> bool {
+    match e {
+        ApiError::PoolError(_) => true,
+        ApiError::DatabaseError(msg) => {
+            let m = msg.to_lowercase();
+            m.contains("connect")
+                || m.contains("timed out")
+                || m.contains("timeout")
+                || m.contains("dns")
+                || m.contains("connection")
+        }
+        _ => false,
+    }
+}
+
+/// Retry `f` on connection-class errors only, with exponential backoff, giving
+/// up once `DB_DEADLINE` is exceeded or `DB_MAX_ATTEMPTS` is reached and
+/// mapping the final failure to a 503-style [`ApiError::ServiceUnavailable`].
+/// This runs synchronously inside the blocking pool, so the `thread::sleep` is
+/// deliberate.
+fn with_retry<T, F>(f: F) -> Result<T, ApiError>
+where
+    F: Fn() -> Result<T, ApiError>,
+{
+    let start = Instant::now();
+    let mut attempt: u32 = 0;
+    loop {
+        match f() {
+            Ok(v) => return Ok(v),
+            Err(e) => {
+                attempt += 1;
+                if !is_connection_error(&e) {
+                    return Err(e);
+                }
+                if attempt >= DB_MAX_ATTEMPTS {
+                    return Err(ApiError::ServiceUnavailable(e.to_string()));
+                }
+                let backoff = DB_BASE_BACKOFF * 2u32.pow(attempt - 1);
+                if start.elapsed() + backoff >= DB_DEADLINE {
+                    return Err(ApiError::ServiceUnavailable(e.to_string()));
+                }
+                tracing::warn!(attempt, kind = "db-retry", "{}", e);
+                thread::sleep(backoff);
+            }
+        }
+    }
+}
+
+/// Run a blocking `arangors` operation on Tokio's blocking thread pool so the
+/// async executor stays free while ArangoDB is slow or the r2d2 pool is
+/// saturated. The operation is retried on transient connection-class failures
+/// (see [`with_retry`]). A join failure (panic inside the closure) is surfaced
+/// as an `ApiError` rejection rather than taking the whole task down.
+pub async fn with_blocking<T, F>(f: F) -> Result<T, warp::Rejection>
+where
+    T: Send + 'static,
+    F: Fn() -> Result<T, ApiError> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(move || with_retry(&f)).await {
+        Ok(r) => r.map_err(warp::reject::custom),
+        Err(e) => Err(warp::reject::custom(ApiError::DatabaseError(e.to_string()))),
+    }
+}
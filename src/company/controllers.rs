@@ -0,0 +1,185 @@
+use arangors::{
+    document::{
+        options::{InsertOptions, RemoveOptions, UpdateOptions},
+        response::DocumentResponse,
+    },
+    AqlQuery, Collection, Database, Document,
+};
+use serde_json::{to_value, Value};
+use std::collections::HashMap;
+use uclient::reqwest::ReqwestClient;
+use warp;
+
+use crate::blocking::with_blocking;
+use crate::config::db_database;
+use crate::database::{DbConn, DbPool};
+use crate::error_handler::ApiError;
+use crate::user::controllers::Paginated;
+use crate::company::{
+    CompanyResponse,
+    CreateCompanyParams,
+    DeleteCompanyParams,
+    FindCompaniesRequest,
+    UpdateCompanyParams,
+};
+
+/// Default page size when the request does not constrain `limit`.
+const DEFAULT_LIMIT: u32 = 20;
+
+/// GET /companies
+pub async fn find_companies(
+    req: FindCompaniesRequest,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = req.offset.unwrap_or(0);
+
+    let (items, total) = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        // Shared FILTER clause so the page and its count stay consistent.
+        let mut filter = String::new();
+        let mut vars: HashMap<&str, Value> = HashMap::new();
+        if let Some(search) = req.search.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            filter.push_str(" FILTER CONTAINS(LOWER(x.name), LOWER(@search))");
+            vars.insert("search", to_value(search).unwrap());
+        }
+
+        // `sort_by` is whitelisted in `with_find_request`, so interpolating it
+        // into the attribute accessor here is safe from injection.
+        let sort = match req.sort_by.as_deref() {
+            Some(field) => format!(" SORT x.{} ASC", field),
+            None => String::new(),
+        };
+
+        let page_q = format!(
+            "FOR x IN companies{}{} LIMIT @offset, @limit RETURN x",
+            filter, sort
+        );
+        let mut page_vars = vars.clone();
+        page_vars.insert("offset", to_value(offset).unwrap());
+        page_vars.insert("limit", to_value(limit).unwrap());
+        let items: Vec<CompanyResponse> = db
+            .aql_query(AqlQuery::builder().query(&page_q).bind_vars(page_vars).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let count_q = format!(
+            "FOR x IN companies{} COLLECT WITH COUNT INTO total RETURN total",
+            filter
+        );
+        let total: Vec<u32> = db
+            .aql_query(AqlQuery::builder().query(&count_q).bind_vars(vars).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok((items, total.into_iter().next().unwrap_or(0)))
+    })
+    .await?;
+
+    Ok(warp::reply::json(&Paginated {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// GET /companies/:key
+pub async fn show_company(
+    key: String,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let company: CompanyResponse = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("companies")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let doc: Document<CompanyResponse> = collection
+            .document(&key)
+            .map_err(|_| ApiError::NotFound(format!("company {} not found", key)))?;
+        Ok(doc.document)
+    })
+    .await?;
+    Ok(warp::reply::json(&company))
+}
+
+/// POST /companies
+pub async fn create_company(
+    params: CreateCompanyParams,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let company: CompanyResponse = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("companies")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let response: DocumentResponse<Document<CompanyResponse>> = collection
+            .create_document(Document::new(params.clone()), InsertOptions::builder().return_new(true).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let company = response
+            .new_doc()
+            .map(|d| d.document.clone())
+            .ok_or_else(|| ApiError::DatabaseError("insert returned no document".to_string()))?;
+        Ok(company)
+    })
+    .await?;
+    Ok(warp::reply::json(&company))
+}
+
+/// PUT /companies/:key
+pub async fn update_company(
+    key: String,
+    params: UpdateCompanyParams,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let company: CompanyResponse = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("companies")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let response: DocumentResponse<Document<CompanyResponse>> = collection
+            .update_document(&key, params.clone(), UpdateOptions::builder().return_new(true).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let company = response
+            .new_doc()
+            .map(|d| d.document.clone())
+            .ok_or_else(|| ApiError::NotFound(format!("company {} not found", key)))?;
+        Ok(company)
+    })
+    .await?;
+    Ok(warp::reply::json(&company))
+}
+
+/// DELETE /companies/:key
+pub async fn delete_company(
+    key: String,
+    _params: DeleteCompanyParams,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("companies")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        collection
+            .remove_document::<CompanyResponse>(&key, RemoveOptions::default(), None)
+            .map_err(|_| ApiError::NotFound(format!("company {} not found", key)))?;
+        Ok(())
+    })
+    .await?;
+    Ok(warp::reply::with_status(warp::reply(), warp::http::StatusCode::NO_CONTENT))
+}
@@ -106,13 +106,24 @@ fn with_find_request() -> impl Filter<Extract = (FindCompaniesRequest, ), Error
                     ));
                 },
             };
-            if limit < 1 && limit > 100 {
+            if limit < 1 || limit > 100 {
                 return Err(warp::reject::custom(
                     ApiError::ParsingError("limit".to_string(), "Must be between 1 and 100".to_string())
                 ));
             }
             req.limit = Some(limit);
         }
+        if params.offset.is_some() {
+            let offset = match params.offset.unwrap().parse::<u32>() {
+                Ok(r) => r,
+                Err(e) => {
+                    return Err(warp::reject::custom(
+                        ApiError::ParsingError("offset".to_string(), e.to_string())
+                    ));
+                },
+            };
+            req.offset = Some(offset);
+        }
         Ok(req)
     })
 }
@@ -128,9 +139,14 @@ async fn validate_create_params(
     let params: CreateCompanyParams = match serde_path_to_error::deserialize(deserializer) {
         Ok(r) => r,
         Err(e) => {
-            let pieces: Vec<String> = e.to_string().as_str().split(": ").map(String::from).collect();
+            // Carry the field path and underlying message as separate fields
+            // instead of reparsing the `Display` string on `": "`, which panics
+            // on nested JSON paths or messages that contain colons.
+            let path = e.path().to_string();
+            let message = e.inner().to_string();
+            tracing::warn!(field = %path, kind = "deserialize", "{}", message);
             return Err(warp::reject::custom(
-                ApiError::ParsingError(pieces[0].clone(), pieces[1].clone())
+                ApiError::DeserializeError { path, message }
             ));
         },
     };
@@ -156,9 +172,14 @@ async fn validate_update_params(
     let params: UpdateCompanyParams = match serde_path_to_error::deserialize(deserializer) {
         Ok(r) => r,
         Err(e) => {
-            let pieces: Vec<String> = e.to_string().as_str().split(": ").map(String::from).collect();
+            // Carry the field path and underlying message as separate fields
+            // instead of reparsing the `Display` string on `": "`, which panics
+            // on nested JSON paths or messages that contain colons.
+            let path = e.path().to_string();
+            let message = e.inner().to_string();
+            tracing::warn!(field = %path, kind = "deserialize", "{}", message);
             return Err(warp::reject::custom(
-                ApiError::ParsingError(pieces[0].clone(), pieces[1].clone())
+                ApiError::DeserializeError { path, message }
             ));
         },
     };
@@ -184,9 +205,14 @@ async fn validate_delete_params(
     let params: DeleteCompanyParams = match serde_path_to_error::deserialize(deserializer) {
         Ok(r) => r,
         Err(e) => {
-            let pieces: Vec<String> = e.to_string().as_str().split(": ").map(String::from).collect();
+            // Carry the field path and underlying message as separate fields
+            // instead of reparsing the `Display` string on `": "`, which panics
+            // on nested JSON paths or messages that contain colons.
+            let path = e.path().to_string();
+            let message = e.inner().to_string();
+            tracing::warn!(field = %path, kind = "deserialize", "{}", message);
             return Err(warp::reject::custom(
-                ApiError::ParsingError(pieces[0].clone(), pieces[1].clone())
+                ApiError::DeserializeError { path, message }
             ));
         },
     };
@@ -0,0 +1,123 @@
+use std::{
+    env,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use warp::Filter;
+
+/// Immutable snapshot of the runtime-tunable settings. A fresh snapshot is
+/// published on reload; request handlers read [`current`] so they always see
+/// the latest values without capturing anything at filter-construction time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settings {
+    /// Name of the ArangoDB database the handlers operate against.
+    pub db_database: String,
+    /// Maximum accepted multipart upload size, in bytes.
+    pub upload_limit: u64,
+    /// Content-types accepted for avatar uploads (e.g. `image/png`).
+    pub image_content_types: Vec<String>,
+    /// Filesystem root under which uploaded avatars are stored.
+    pub storage_dir: PathBuf,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            db_database: env::var("DB_DATABASE").unwrap_or_else(|_| "groupware".to_string()),
+            upload_limit: 5_000_000,
+            image_content_types: vec![
+                "image/png".to_string(),
+                "image/jpeg".to_string(),
+                "image/gif".to_string(),
+                "image/webp".to_string(),
+            ],
+            storage_dir: env::current_dir().unwrap_or_default().join("storage"),
+        }
+    }
+}
+
+/// Path of the source file the snapshot is (re)loaded from.
+fn source_path() -> PathBuf {
+    PathBuf::from(env::var("CONFIG_PATH").unwrap_or_else(|_| "config.json".to_string()))
+}
+
+/// Read and parse the source file, falling back to [`Settings::default`] when
+/// it is missing or unreadable so the service still starts with sane values.
+fn load() -> Settings {
+    match std::fs::read_to_string(source_path()) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!(kind = "config", "invalid config, using defaults: {}", e);
+                Settings::default()
+            }
+        },
+        Err(_) => Settings::default(),
+    }
+}
+
+static SETTINGS: Lazy<ArcSwap<Settings>> = Lazy::new(|| ArcSwap::from_pointee(load()));
+
+/// Current settings snapshot. Cheap to call on every request.
+pub fn current() -> Arc<Settings> {
+    SETTINGS.load_full()
+}
+
+/// Re-read the source file and publish a new snapshot atomically.
+pub fn reload() {
+    SETTINGS.store(Arc::new(load()));
+    tracing::info!(kind = "config", "configuration reloaded");
+}
+
+/// Name of the ArangoDB database the handlers operate against.
+pub fn db_database() -> String {
+    current().db_database.clone()
+}
+
+/// Install a SIGHUP handler that reloads the configuration in place.
+#[cfg(unix)]
+pub fn install_sighup_handler() {
+    use tokio::signal::unix::{signal, SignalKind};
+    tokio::spawn(async move {
+        let mut stream = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(kind = "config", "cannot listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+        while stream.recv().await.is_some() {
+            reload();
+        }
+    });
+}
+
+/// POST /admin/reload — reload the configuration without a restart.
+///
+/// This is an operator-only control endpoint. It is gated by a shared secret
+/// supplied in the `X-Admin-Token` header and matched against the `ADMIN_TOKEN`
+/// environment variable; the endpoint fails closed, so when `ADMIN_TOKEN` is
+/// unset every request is rejected. It is still expected to sit behind the
+/// usual operator controls (internal network / ingress auth) — the token is a
+/// defence-in-depth backstop, not the only gate.
+pub fn admin_reload() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "reload")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("x-admin-token"))
+        .and_then(|token: Option<String>| async move {
+            let expected = env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+            match (expected, token) {
+                (Some(expected), Some(token)) if token == expected => {
+                    reload();
+                    Ok(warp::reply::json(&serde_json::json!({ "reloaded": true })))
+                }
+                _ => Err(warp::reject::custom(crate::error_handler::ApiError::Unauthorized(
+                    "admin token required".to_string(),
+                ))),
+            }
+        })
+}
@@ -0,0 +1,82 @@
+use std::{
+    convert::Infallible,
+    fmt,
+};
+
+use serde_json::json;
+use validator::ValidationErrors;
+use warp::{
+    http::StatusCode,
+    reject::Reject,
+    Rejection, Reply,
+};
+
+/// Application-level error surfaced as a warp rejection and recovered into a
+/// JSON response by [`recover`]. Connection-class variants map to 5xx; the
+/// retry/backoff path in the blocking executor tells them apart by variant.
+#[derive(Debug)]
+pub enum ApiError {
+    /// A field failed to parse; carries the field name and a message.
+    ParsingError(String, String),
+    /// `validator` rejected the deserialized params.
+    ValidationError(ValidationErrors),
+    /// A deserialize failure with the structured field path and message kept
+    /// separate rather than reparsed out of the `Display` string.
+    DeserializeError { path: String, message: String },
+    /// r2d2 pool checkout failed or timed out.
+    PoolError(String),
+    /// The underlying ArangoDB/reqwest client returned an error.
+    DatabaseError(String),
+    /// Retries were exhausted against a connection-class failure.
+    ServiceUnavailable(String),
+    /// No matching document was found.
+    NotFound(String),
+    /// The caller is not authorized to perform the request.
+    Unauthorized(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::ParsingError(field, message) => write!(f, "{}: {}", field, message),
+            ApiError::ValidationError(e) => write!(f, "{}", e),
+            ApiError::DeserializeError { path, message } => write!(f, "{}: {}", path, message),
+            ApiError::PoolError(message) => write!(f, "{}", message),
+            ApiError::DatabaseError(message) => write!(f, "{}", message),
+            ApiError::ServiceUnavailable(message) => write!(f, "{}", message),
+            ApiError::NotFound(message) => write!(f, "{}", message),
+            ApiError::Unauthorized(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl Reject for ApiError {}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::ParsingError(_, _) | ApiError::DeserializeError { .. } => StatusCode::BAD_REQUEST,
+            ApiError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::PoolError(_) | ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Recover an [`ApiError`] (or an unhandled rejection) into a JSON error
+/// response. Mount with `.recover(error_handler::recover)` at the top level.
+pub async fn recover(err: Rejection) -> Result<impl Reply, Infallible> {
+    let (code, message) = if let Some(e) = err.find::<ApiError>() {
+        (e.status(), e.to_string())
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "Not Found".to_string())
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error".to_string())
+    };
+    let body = warp::reply::json(&json!({ "error": message }));
+    Ok(warp::reply::with_status(body, code))
+}
@@ -1,52 +1,283 @@
 use arangors::{
     document::{
-        options::{InsertOptions, RemoveOptions, UpdateOptions},
+        options::{InsertOptions, UpdateOptions},
         response::DocumentResponse,
     },
     AqlQuery, Collection, Database, Document,
 };
-use serde_json::Value;
-use std::{
-    collections::HashMap,
-    convert::Infallible,
-};
+use serde::{Deserialize, Serialize};
+use serde_json::{to_value, Value};
+use std::collections::HashMap;
 use uclient::reqwest::ReqwestClient;
 use warp;
 
+use crate::blocking::with_blocking;
 use crate::config::db_database;
 use crate::database::{DbConn, DbPool};
-use crate::user::UserResponse;
+use crate::error_handler::ApiError;
+use crate::user::{CreateUserParams, FindUsersRequest, UpdateUserParams, UserResponse};
+
+/// Paginated list envelope returned by the find handlers.
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// Default page size when the request does not constrain `limit`.
+const DEFAULT_LIMIT: u32 = 20;
 
 pub async fn find_users(
+    req: FindUsersRequest,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = req.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = req.offset.unwrap_or(0);
+
+    // `arangors`/`ReqwestClient` I/O is fully synchronous, so run the pool
+    // checkout and the AQL round-trips on the blocking pool rather than
+    // stalling the Tokio worker that drives the warp event loop.
+    let (items, total) = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        // Shared FILTER clause so the page and its count stay consistent.
+        let mut filter = String::new();
+        let mut vars: HashMap<&str, Value> = HashMap::new();
+        if let Some(search) = req.search.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            filter.push_str(" FILTER CONTAINS(LOWER(x.name), LOWER(@search))");
+            vars.insert("search", to_value(search).unwrap());
+        }
+
+        // `sort_by` is whitelisted in `with_find_request`, so interpolating it
+        // into the attribute accessor here is safe from injection.
+        let sort = match req.sort_by.as_deref() {
+            Some(field) => format!(" SORT x.{} ASC", field),
+            None => String::new(),
+        };
+
+        let page_q = format!(
+            "FOR x IN users{}{} LIMIT @offset, @limit RETURN x",
+            filter, sort
+        );
+        let mut page_vars = vars.clone();
+        page_vars.insert("offset", to_value(offset).unwrap());
+        page_vars.insert("limit", to_value(limit).unwrap());
+        let items: Vec<UserResponse> = db
+            .aql_query(AqlQuery::builder().query(&page_q).bind_vars(page_vars).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let count_q = format!(
+            "FOR x IN users{} COLLECT WITH COUNT INTO total RETURN total",
+            filter
+        );
+        let total: Vec<u32> = db
+            .aql_query(AqlQuery::builder().query(&count_q).bind_vars(vars).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok((items, total.into_iter().next().unwrap_or(0)))
+    })
+    .await?;
+
+    Ok(warp::reply::json(&Paginated {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Query parameters for the `/.well-known/groupware.json` discovery endpoint.
+/// Either `name` or `email` may be supplied to resolve a handle to a profile.
+#[derive(Debug, Deserialize)]
+pub struct DiscoverUserParams {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Trimmed public projection of a user returned by the discovery endpoint —
+/// deliberately excludes password fields.
+#[derive(Debug, Serialize)]
+pub struct PublicUser {
+    pub key: String,
+    pub name: String,
+    pub avatar: Option<String>,
+}
+
+/// GET /.well-known/groupware.json — resolve a username/email to a public
+/// profile via a parameterized AQL `FILTER` lookup against the users
+/// collection. Mirrors federated-identity well-known JSON discovery.
+pub async fn discover_user(
+    params: DiscoverUserParams,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (field, needle): (&str, String) = match (params.name, params.email) {
+        (Some(name), _) => ("name", name),
+        (_, Some(email)) => ("email", email),
+        _ => {
+            return Err(warp::reject::custom(ApiError::ParsingError(
+                "name".to_string(),
+                "One of name or email is required".to_string(),
+            )));
+        }
+    };
+
+    let user: Option<PublicUser> = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let q = format!(
+            "FOR x IN users FILTER x.{} == @needle LIMIT 1 \
+             RETURN {{ key: x._key, name: x.name, avatar: x.avatar }}",
+            field
+        );
+        let mut vars: HashMap<&str, Value> = HashMap::new();
+        vars.insert("needle", to_value(needle.clone()).unwrap());
+        let aql = AqlQuery::builder().query(&q).bind_vars(vars).build();
+        let mut records: Vec<PublicUser> = db
+            .aql_query(aql)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        Ok(records.drain(..).next())
+    })
+    .await?;
+
+    match user {
+        Some(u) => Ok(warp::reply::json(&u)),
+        None => Err(warp::reject::custom(ApiError::NotFound(
+            "No matching user".to_string(),
+        ))),
+    }
+}
+
+/// GET /users/:key
+pub async fn show_user(
+    key: String,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let user: UserResponse = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("users")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let doc: Document<UserResponse> = collection
+            .document(&key)
+            .map_err(|_| ApiError::NotFound(format!("user {} not found", key)))?;
+        Ok(doc.document)
+    })
+    .await?;
+    Ok(warp::reply::json(&user))
+}
+
+/// POST /users
+pub async fn create_user(
+    params: CreateUserParams,
+    avatar_hash: Option<String>,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let user: Value = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("users")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let doc = document_with_hash(&params, avatar_hash.as_deref())?;
+        let response: DocumentResponse<Document<Value>> = collection
+            .create_document(Document::new(doc), InsertOptions::builder().return_new(true).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let user = response
+            .new_doc()
+            .map(|d| d.document.clone())
+            .ok_or_else(|| ApiError::DatabaseError("insert returned no document".to_string()))?;
+        Ok(user)
+    })
+    .await?;
+    Ok(warp::reply::json(&user))
+}
+
+/// PUT /users/:key
+pub async fn update_user(
+    key: String,
+    params: UpdateUserParams,
+    avatar_hash: Option<String>,
+    pool: DbPool,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let user: Value = with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let collection: Collection<ReqwestClient> = db
+            .collection("users")
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let doc = document_with_hash(&params, avatar_hash.as_deref())?;
+        let response: DocumentResponse<Document<Value>> = collection
+            .update_document(&key, doc, UpdateOptions::builder().return_new(true).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let user = response
+            .new_doc()
+            .map(|d| d.document.clone())
+            .ok_or_else(|| ApiError::NotFound(format!("user {} not found", key)))?;
+        Ok(user)
+    })
+    .await?;
+    Ok(warp::reply::json(&user))
+}
+
+/// Serialize the validated params into a document object, injecting the
+/// computed avatar dHash under `avatar_hash` so it is persisted alongside the
+/// avatar path and can be matched against on later uploads.
+fn document_with_hash<T: Serialize>(params: &T, avatar_hash: Option<&str>) -> Result<Value, ApiError> {
+    let mut doc = to_value(params).map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if let (Some(hash), Some(obj)) = (avatar_hash, doc.as_object_mut()) {
+        obj.insert("avatar_hash".to_string(), Value::String(hash.to_string()));
+    }
+    Ok(doc)
+}
+
+/// An existing avatar path and the dHash persisted with it.
+#[derive(Debug, Deserialize)]
+struct StoredAvatar {
+    avatar: String,
+    avatar_hash: String,
+}
+
+/// Find an already-stored avatar whose persisted dHash is within `threshold`
+/// Hamming distance of `hash`, querying the users collection instead of
+/// re-decoding every file on disk. Returns the reusable `/storage/...` path.
+pub(crate) async fn find_duplicate_avatar(
     pool: DbPool,
-) -> Result<impl warp::Reply, Infallible> {
-    let conn: DbConn = pool.get().unwrap();
-    let db: Database<ReqwestClient> = conn.db(&db_database()).unwrap();
-    let mut terms = vec!["FOR x IN companies"];
-    let mut vars: HashMap<&str, Value> = HashMap::new();
-    // if params.search.is_some() {
-    //     let search: String = params.search.unwrap().trim().to_string();
-    //     if !search.is_empty() {
-    //         terms.push("FILTER CONTAINS(x.name, @@search)");
-    //         vars.insert("@search", to_value(search).unwrap());
-    //     }
-    // }
-    // if params.sort_by.is_some() {
-    //     let sort_by: String = params.sort_by.unwrap();
-    //     terms.push("SORT x.@@sort_by ASC");
-    //     vars.insert("@sort_by", to_value(sort_by).unwrap());
-    // }
-    // if params.limit.is_some() {
-    //     let limit: u32 = params.limit.unwrap();
-    //     terms.push("LIMIT 0, @@limit");
-    //     vars.insert("@limit", to_value(limit).unwrap());
-    // }
-    terms.push("RETURN x");
-    let q = terms.join(" ");
-    let aql = AqlQuery::builder()
-        .query(&q)
-        .bind_vars(vars)
-        .build();
-    let records: Vec<UserResponse> = db.aql_query(aql).expect("Query failed");
-    Ok(warp::reply::json(&records))
+    hash: u64,
+    threshold: u32,
+) -> Result<Option<String>, warp::Rejection> {
+    with_blocking(move || {
+        let conn: DbConn = pool.get().map_err(|e| ApiError::PoolError(e.to_string()))?;
+        let db: Database<ReqwestClient> = conn
+            .db(&db_database())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let q = "FOR x IN users FILTER x.avatar != null AND x.avatar_hash != null \
+                 RETURN { avatar: x.avatar, avatar_hash: x.avatar_hash }";
+        let vars: HashMap<&str, Value> = HashMap::new();
+        let rows: Vec<StoredAvatar> = db
+            .aql_query(AqlQuery::builder().query(q).bind_vars(vars).build())
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        for row in rows {
+            if let Ok(existing) = row.avatar_hash.parse::<u64>() {
+                if (existing ^ hash).count_ones() <= threshold {
+                    return Ok(Some(row.avatar));
+                }
+            }
+        }
+        Ok(None)
+    })
+    .await
 }
\ No newline at end of file
@@ -1,7 +1,6 @@
 use bytes::BufMut;
 use std::{
     collections::HashMap,
-    env,
     ffi::OsStr,
     path::Path,
 };
@@ -19,6 +18,7 @@ use crate::error_handler::ApiError;
 use crate::user::{
     self,
     CreateUserParams,
+    DiscoverUserParams,
     FindUsersParams,
     FindUsersRequest,
     UpdateUserParams,
@@ -27,10 +27,27 @@ use crate::user::{
 pub fn init(
     pool: DbPool,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    // Start listening for SIGHUP so operators can reload configuration in
+    // place, and expose the equivalent POST /admin/reload trigger.
+    #[cfg(unix)]
+    crate::config::install_sighup_handler();
     find_users(pool.clone())
+        .or(discover_user(pool.clone()))
         .or(show_user(pool.clone()))
         .or(create_user(pool.clone()))
         .or(update_user(pool))
+        .or(crate::config::admin_reload())
+}
+
+/// GET /.well-known/groupware.json?name=<username>&email=<email>
+fn discover_user(
+    pool: DbPool,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!(".well-known" / "groupware.json")
+        .and(warp::get())
+        .and(warp::query::<DiscoverUserParams>())
+        .and(with_db(pool))
+        .and_then(user::discover_user)
 }
 
 /// GET /users
@@ -60,7 +77,7 @@ fn create_user(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("users")
         .and(warp::post())
-        .and(with_create_params())
+        .and(with_create_params(pool.clone()))
         .and(with_db(pool))
         .and_then(user::create_user)
 }
@@ -71,7 +88,7 @@ fn update_user(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("users" / String)
         .and(warp::put())
-        .and(with_update_params())
+        .and(with_update_params(pool.clone()))
         .and(with_db(pool))
         .and_then(user::update_user)
 }
@@ -107,32 +124,55 @@ fn with_find_request() -> impl Filter<Extract = (FindUsersRequest, ), Error = wa
                     ));
                 },
             };
-            if limit < 1 && limit > 100 {
+            if limit < 1 || limit > 100 {
                 return Err(warp::reject::custom(
                     ApiError::ParsingError("limit".to_string(), "Must be between 1 and 100".to_string())
                 ));
             }
             req.limit = Some(limit);
         }
+        if params.offset.is_some() {
+            let offset = match params.offset.unwrap().parse::<u32>() {
+                Ok(r) => r,
+                Err(e) => {
+                    return Err(warp::reject::custom(
+                        ApiError::ParsingError("offset".to_string(), e.to_string())
+                    ));
+                },
+            };
+            req.offset = Some(offset);
+        }
         Ok(req)
     })
 }
 
-fn with_create_params() -> impl Filter<Extract = (CreateUserParams, ), Error = warp::Rejection> + Clone {
-    warp::multipart::form().max_length(5_000_000).and_then(validate_create_params)
+/// Generous static upper bound the multipart parser enforces while the body is
+/// still streaming, so an oversized part is rejected before it is buffered into
+/// memory. The live, reloadable limit (`config::current().upload_limit`) is the real
+/// policy and is re-checked per request in `accept_uploading`; this constant is
+/// only a DoS backstop and is deliberately well above any configured value.
+const MAX_MULTIPART_BYTES: u64 = 64 * 1024 * 1024;
+
+fn with_create_params(pool: DbPool) -> impl Filter<Extract = (CreateUserParams, Option<String>), Error = warp::Rejection> + Clone {
+    warp::multipart::form()
+        .max_length(MAX_MULTIPART_BYTES)
+        .and(with_db(pool))
+        .and_then(validate_create_params)
 }
 
 async fn validate_create_params(
     form: FormData,
-) -> Result<CreateUserParams, warp::Rejection> {
+    pool: DbPool,
+) -> Result<(CreateUserParams, Option<String>), warp::Rejection> {
     let parts: Vec<Part> = form.try_collect().await.map_err(|e| {
-        println!("{:?}", e);
+        tracing::warn!(kind = "multipart", "{}", e);
         warp::reject::custom(
-            ApiError::ParsingError("sort_by".to_string(), "Must be one of name and email".to_string())
+            ApiError::ParsingError("body".to_string(), format!("malformed multipart body: {}", e))
         )
-    }).unwrap();
+    })?;
 
-    let vars: HashMap<String, String> = accept_uploading(parts).await.unwrap();
+    let vars: HashMap<String, String> = accept_uploading(parts, pool).await?;
+    let avatar_hash = vars.get("avatar_hash").cloned();
 
     let params = CreateUserParams {
         name: if vars.contains_key("name") {
@@ -162,7 +202,7 @@ async fn validate_create_params(
         },
     };
     match params.validate() {
-        Ok(_) => Ok(params),
+        Ok(_) => Ok((params, avatar_hash)),
         Err(e) => {
             Err(warp::reject::custom(
                 ApiError::ValidationError(e)
@@ -171,22 +211,26 @@ async fn validate_create_params(
     }
 }
 
-fn with_update_params() -> impl Filter<Extract = (UpdateUserParams, ), Error = warp::Rejection> + Clone {
-    warp::multipart::form().max_length(5_000_000).and_then(validate_update_params)
+fn with_update_params(pool: DbPool) -> impl Filter<Extract = (UpdateUserParams, Option<String>), Error = warp::Rejection> + Clone {
+    warp::multipart::form()
+        .max_length(MAX_MULTIPART_BYTES)
+        .and(with_db(pool))
+        .and_then(validate_update_params)
 }
 
 async fn validate_update_params(
     form: FormData,
-) -> Result<UpdateUserParams, warp::Rejection> {
-    println!("123");
+    pool: DbPool,
+) -> Result<(UpdateUserParams, Option<String>), warp::Rejection> {
     let parts: Vec<Part> = form.try_collect().await.map_err(|e| {
-        println!("{:?}", e);
+        tracing::warn!(kind = "multipart", "{}", e);
         warp::reject::custom(
-            ApiError::ParsingError("sort_by".to_string(), "Must be one of name and email".to_string())
+            ApiError::ParsingError("body".to_string(), format!("malformed multipart body: {}", e))
         )
-    }).unwrap();
+    })?;
 
-    let vars: HashMap<String, String> = accept_uploading(parts).await.unwrap();
+    let vars: HashMap<String, String> = accept_uploading(parts, pool).await?;
+    let avatar_hash = vars.get("avatar_hash").cloned();
 
     let params = UpdateUserParams {
         name: if vars.contains_key("name") {
@@ -216,7 +260,7 @@ async fn validate_update_params(
         },
     };
     match params.validate() {
-        Ok(_) => Ok(params),
+        Ok(_) => Ok((params, avatar_hash)),
         Err(e) => {
             Err(warp::reject::custom(
                 ApiError::ValidationError(e)
@@ -227,16 +271,28 @@ async fn validate_update_params(
 
 async fn accept_uploading(
     parts: Vec<Part>,
+    pool: DbPool,
 ) -> Result<HashMap<String, String>, warp::Rejection> {
     let mut vars: HashMap<String, String> = HashMap::new();
     for p in parts {
         let field_name = p.name().clone().to_string();
         let org_filename = p.filename().clone();
         let mut file_extension: Option<String> = None;
-        if org_filename.is_some() {
-            let content_type = p.content_type().unwrap();
-            if content_type.starts_with("image/") {
-                file_extension = Some(Path::new(org_filename.unwrap()).extension().and_then(OsStr::to_str).unwrap().to_string());
+        if let Some(filename) = org_filename {
+            let content_type = p.content_type().ok_or_else(|| {
+                warp::reject::custom(
+                    ApiError::ParsingError("avatar".to_string(), "file part is missing a Content-Type".to_string())
+                )
+            })?;
+            // Accept only the content-types allowed by the current config
+            // snapshot, so a live reload can widen or narrow the set.
+            if crate::config::current().image_content_types.iter().any(|t| t == content_type) {
+                let extension = Path::new(filename).extension().and_then(OsStr::to_str).ok_or_else(|| {
+                    warp::reject::custom(
+                        ApiError::ParsingError("avatar".to_string(), format!("file '{}' has no extension", filename))
+                    )
+                })?;
+                file_extension = Some(extension.to_string());
             } else {
                 let msg = format!("invalid file type found: {}", content_type);
                 return Err(warp::reject::custom(
@@ -253,24 +309,99 @@ async fn accept_uploading(
             warp::reject::custom(
                 ApiError::ParsingError("avatar".to_string(), msg)
             )
-        }).unwrap();
+        })?;
+
+        // Re-check the configurable limit against the live snapshot so a reload
+        // that tightened `upload_limit` rejects oversized parts immediately.
+        let upload_limit = crate::config::current().upload_limit;
+        if value.len() as u64 > upload_limit {
+            let msg = format!("upload exceeds limit of {} bytes", upload_limit);
+            return Err(warp::reject::custom(
+                ApiError::ParsingError(field_name, msg)
+            ));
+        }
 
         if file_extension.is_some() {
-            let mut abs_filepath = env::current_dir().unwrap();
-            abs_filepath.push("storage");
+            let storage_dir = crate::config::current().storage_dir.clone();
+
+            // Decode the uploaded image and compute its dHash. Only the single
+            // (size-capped) upload is decoded — the old per-file scan of the
+            // whole avatar store is gone. Run it on the blocking pool and hand
+            // the bytes back so we can still write them below.
+            let (value, hash) = tokio::task::spawn_blocking(move || {
+                let result = difference_hash(&value);
+                (value, result)
+            }).await.map_err(|e| {
+                warp::reject::custom(
+                    ApiError::ParsingError("avatar".to_string(), e.to_string())
+                )
+            })?;
+            let hash = hash.map_err(|e| {
+                let msg = format!("error decoding image: {}", e);
+                warp::reject::custom(
+                    ApiError::ParsingError("avatar".to_string(), msg)
+                )
+            })?;
+
+            // Look candidates up via the DB, comparing against the hashes
+            // persisted on existing user documents rather than re-reading files.
+            if let Some(existing) =
+                crate::user::controllers::find_duplicate_avatar(pool.clone(), hash, AVATAR_HASH_THRESHOLD).await?
+            {
+                vars.insert(format!("{}_hash", field_name), hash.to_string());
+                vars.insert(field_name, existing);
+                continue;
+            }
+
             let new_filename = format!("{}.{}", Uuid::new_v4().to_string(), file_extension.unwrap().as_str());
+            let mut abs_filepath = storage_dir.clone();
             abs_filepath.push(new_filename.clone());
             tokio::fs::write(&abs_filepath, value).await.map_err(|e| {
                 let msg = format!("error writing file: {}", e);
                 warp::reject::custom(
                     ApiError::ParsingError("avatar".to_string(), msg)
                 )
-            }).unwrap();
+            })?;
             let rel_filepath = format!("/storage/{}", new_filename);
+            // Persist the hash alongside the path so future uploads can dedup
+            // against it via the DB (see `find_duplicate_avatar`).
+            vars.insert(format!("{}_hash", field_name), hash.to_string());
             vars.insert(field_name, rel_filepath);
         } else {
-            vars.insert(field_name, String::from_utf8(value).unwrap());
+            let text = String::from_utf8(value).map_err(|e| {
+                warp::reject::custom(
+                    ApiError::ParsingError(field_name.clone(), format!("invalid utf-8 field: {}", e))
+                )
+            })?;
+            vars.insert(field_name, text);
         }
     }
     Ok(vars)
 }
+
+/// Maximum Hamming distance between two dHashes for two avatars to count as the
+/// same picture. Tuned conservatively so only near-identical re-uploads dedup.
+const AVATAR_HASH_THRESHOLD: u32 = 5;
+
+/// Compute the 64-bit difference hash (dHash) of an encoded image: resize to
+/// 9x8 grayscale and, for each of the 8 rows, emit one bit per adjacent-pixel
+/// pair — set when the left pixel is brighter than the right — packing the 64
+/// comparisons into a `u64`.
+fn difference_hash(bytes: &[u8]) -> Result<u64, image::ImageError> {
+    let gray = image::load_from_memory(bytes)?
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .to_luma8();
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = gray.get_pixel(x, y).0[0];
+            let right = gray.get_pixel(x + 1, y).0[0];
+            hash <<= 1;
+            if left > right {
+                hash |= 1;
+            }
+        }
+    }
+    Ok(hash)
+}
+